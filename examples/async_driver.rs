@@ -0,0 +1,42 @@
+//! Demonstrates driving the editor's `async` input path
+//! (`kilo::platform::unix::unblocking::Terminal`) alongside another future
+//! in one `select!`, the scenario `unblocking` exists for: integrators who
+//! need to `.await` a key alongside timers or other I/O instead of owning
+//! the run loop outright.
+//!
+//! Run with: `cargo run --example async_driver`
+
+extern crate kilo;
+extern crate tokio;
+
+use kilo::platform::unix::unblocking::Terminal;
+use kilo::Key;
+use tokio::time::{self, Duration};
+
+#[tokio::main]
+async fn main() -> Result<(), std::io::Error> {
+    let mut term = Terminal::new_raw_mode()?;
+    term.begin();
+
+    let mut ticker = time::interval(Duration::from_secs(1));
+    let mut ticks = 0;
+
+    loop {
+        tokio::select! {
+            key = term.read_key() => {
+                match key? {
+                    Key::Char(b'q') => break,
+                    _ => {}
+                }
+            }
+            _ = ticker.tick() => {
+                ticks += 1;
+                if ticks >= 60 {
+                    break;
+                }
+            }
+        }
+    }
+
+    term.end()
+}
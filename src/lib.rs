@@ -0,0 +1,1372 @@
+use std::cmp;
+use std::env;
+use std::fs;
+use std::io;
+use std::io::prelude::*;
+use std::path;
+use std::str;
+use std::time::{Duration, Instant};
+
+const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+
+const TAB_STOP: usize = 8;
+
+const CTRL: u8 = 0x1f;
+const CTRL_Q: u8 = CTRL & b'q';
+const CTRL_S: u8 = CTRL & b's';
+const CTRL_F: u8 = CTRL & b'f';
+const CTRL_H: u8 = CTRL & b'h';
+const BACKSPACE: u8 = 127;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Key {
+    Char(u8),
+    Escape,
+    Left,
+    Right,
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Delete,
+}
+
+/// Converts a raw cursor column `cx` (a byte offset into `row`) into a
+/// render column `rx`, expanding any tabs up to `cx` to the next multiple
+/// of `TAB_STOP`. Walks whole `char`s so a multi-byte UTF-8 sequence is
+/// never split.
+fn cx_to_rx(row: &str, cx: usize) -> usize {
+    let mut rx = 0;
+    let mut consumed = 0;
+    for ch in row.chars() {
+        if consumed >= cx {
+            break;
+        }
+        if ch == '\t' {
+            rx += (TAB_STOP - 1) - (rx % TAB_STOP);
+        }
+        rx += 1;
+        consumed += ch.len_utf8();
+    }
+    rx
+}
+
+/// Expands the tabs in `row` into the spaces that `draw_rows` actually
+/// puts on screen, carrying the per-byte `Highlight` of `hl` along so the
+/// expanded spaces inherit the category of the tab that produced them.
+/// Returns one `char` (not byte) per render column, so multi-byte UTF-8
+/// text can be sliced by column without landing mid-character.
+fn render_row(row: &str, hl: &[Highlight]) -> (Vec<char>, Vec<Highlight>) {
+    let mut rendered = Vec::new();
+    let mut rhl = Vec::with_capacity(hl.len());
+    let mut byte_idx = 0;
+    for ch in row.chars() {
+        let h = hl.get(byte_idx).cloned().unwrap_or(Highlight::Normal);
+        if ch == '\t' {
+            rendered.push(' ');
+            rhl.push(h);
+            while rendered.len() % TAB_STOP != 0 {
+                rendered.push(' ');
+                rhl.push(h);
+            }
+        } else {
+            rendered.push(ch);
+            rhl.push(h);
+        }
+        byte_idx += ch.len_utf8();
+    }
+    (rendered, rhl)
+}
+
+/// The category a rendered byte falls into, used to color `draw_rows`'
+/// output. Carries its own SGR foreground color code.
+#[derive(Clone, Copy, PartialEq)]
+enum Highlight {
+    Normal,
+    Number,
+    String,
+    Comment,
+    Keyword1,
+    Keyword2,
+    Match,
+}
+
+impl Highlight {
+    fn color(self) -> u8 {
+        match self {
+            Highlight::Normal => 39,
+            Highlight::Number => 31,
+            Highlight::String => 35,
+            Highlight::Comment => 36,
+            Highlight::Keyword1 => 33,
+            Highlight::Keyword2 => 32,
+            Highlight::Match => 34,
+        }
+    }
+}
+
+/// A language's highlighting rules, selected by file extension.
+struct Syntax {
+    extensions: &'static [&'static str],
+    line_comment: &'static str,
+    keywords1: &'static [&'static str],
+    keywords2: &'static [&'static str],
+}
+
+const C_HL_EXTENSIONS: &'static [&'static str] = &[".c", ".h", ".cpp"];
+const C_HL_KEYWORDS1: &'static [&'static str] = &[
+    "switch", "if", "while", "for", "break", "continue", "return", "else", "struct", "union",
+    "typedef", "static", "enum", "class", "case",
+];
+const C_HL_KEYWORDS2: &'static [&'static str] = &[
+    "int", "long", "double", "float", "char", "unsigned", "signed", "void",
+];
+
+static SYNTAXES: &'static [Syntax] = &[
+    Syntax {
+        extensions: C_HL_EXTENSIONS,
+        line_comment: "//",
+        keywords1: C_HL_KEYWORDS1,
+        keywords2: C_HL_KEYWORDS2,
+    },
+];
+
+fn syntax_for_path(path: &path::Path) -> Option<&'static Syntax> {
+    let extension = path.extension().and_then(|ext| ext.to_str())?;
+    let dotted = format!(".{}", extension);
+    SYNTAXES
+        .iter()
+        .find(|syntax| syntax.extensions.contains(&dotted.as_str()))
+}
+
+fn is_separator(b: u8) -> bool {
+    match b {
+        b' ' | b'\t' | 0 => true,
+        b',' | b'.' | b'(' | b')' | b'+' | b'-' | b'/' | b'*' | b'=' | b'~' | b'%' | b'<'
+        | b'>' | b'[' | b']' | b';' | b'{' | b'}' | b':' | b'&' | b'|' | b'!' | b'"' | b'\''
+            => true,
+        _ => false,
+    }
+}
+
+fn keyword_match(rest: &str, keyword: &str) -> bool {
+    rest.starts_with(keyword)
+        && rest
+            .as_bytes()
+            .get(keyword.len())
+            .map_or(true, |&b| is_separator(b))
+}
+
+/// Classifies each byte of `row` into a `Highlight` category, according to
+/// `syntax` (or leaves everything `Normal` with no syntax selected).
+fn highlight_row(row: &str, syntax: Option<&Syntax>) -> Vec<Highlight> {
+    let bytes = row.as_bytes();
+    let mut hl = vec![Highlight::Normal; bytes.len()];
+
+    let syntax = match syntax {
+        Some(syntax) => syntax,
+        None => return hl,
+    };
+
+    // Char boundaries only: `i` is a byte offset, but it must never land
+    // inside a multi-byte UTF-8 sequence, or `&row[i..]` below panics. We
+    // still classify one `Highlight` per logical char, keyed at its
+    // leading byte (continuation bytes stay `Normal`, except inside a line
+    // comment, which marks every byte to the end of the row), same
+    // convention `render_row` uses to look the highlight back up.
+    let boundaries: Vec<usize> = row.char_indices().map(|(i, _)| i).collect();
+    let mut pos = 0;
+    let mut in_string: Option<u8> = None;
+    let mut prev_sep = true;
+
+    while pos < boundaries.len() {
+        let i = boundaries[pos];
+        let b = bytes[i];
+        let prev_hl = if i > 0 { hl[i - 1] } else { Highlight::Normal };
+
+        if in_string.is_none() && !syntax.line_comment.is_empty()
+            && row[i..].starts_with(syntax.line_comment)
+        {
+            for h in hl[i..].iter_mut() {
+                *h = Highlight::Comment;
+            }
+            break;
+        }
+
+        if let Some(quote) = in_string {
+            hl[i] = Highlight::String;
+            if b == b'\\' && pos + 1 < boundaries.len() {
+                hl[boundaries[pos + 1]] = Highlight::String;
+                pos += 2;
+                continue;
+            }
+            if b == quote {
+                in_string = None;
+            }
+            prev_sep = true;
+            pos += 1;
+            continue;
+        } else if b == b'"' || b == b'\'' {
+            in_string = Some(b);
+            hl[i] = Highlight::String;
+            pos += 1;
+            continue;
+        }
+
+        if (b.is_ascii_digit() && (prev_sep || prev_hl == Highlight::Number))
+            || (b == b'.' && prev_hl == Highlight::Number)
+        {
+            hl[i] = Highlight::Number;
+            prev_sep = false;
+            pos += 1;
+            continue;
+        }
+
+        if prev_sep {
+            let rest = &row[i..];
+            let keyword = syntax
+                .keywords1
+                .iter()
+                .map(|kw| (kw, Highlight::Keyword1))
+                .chain(syntax.keywords2.iter().map(|kw| (kw, Highlight::Keyword2)))
+                .find(|&(kw, _)| keyword_match(rest, kw));
+
+            if let Some((kw, category)) = keyword {
+                for h in hl[i..i + kw.len()].iter_mut() {
+                    *h = category;
+                }
+                // Keywords are plain ASCII, so each of their bytes is its
+                // own char boundary: advancing `pos` by `kw.len()` lands
+                // back on a boundary too.
+                pos += kw.len();
+                prev_sep = false;
+                continue;
+            }
+        }
+
+        prev_sep = is_separator(b);
+        pos += 1;
+    }
+
+    hl
+}
+
+pub struct Editor {
+    term: target::Terminal,
+    // Never read: kept alive purely for its `Drop` impl, which restores
+    // the original screen buffer when the editor exits.
+    #[allow(dead_code)]
+    screen: ScreenGuard,
+    screen_rows: usize,
+    screen_cols: usize,
+    cursor_row: usize,
+    cursor_col: usize,
+    row_offset: usize,
+    col_offset: usize,
+    rows: Vec<String>,
+    path: Option<path::PathBuf>,
+    syntax: Option<&'static Syntax>,
+    dirty: bool,
+    quit_pending: bool,
+    message: String,
+    message_time: Instant,
+    search_match: Option<(usize, usize, usize)>,
+    pending_utf8: Vec<u8>,
+}
+
+impl Editor {
+    pub fn new() -> Result<Editor, io::Error> {
+        let term = target::Terminal::new_raw_mode()?;
+        let screen = ScreenGuard::new()?;
+        let (rows, cols) = term.get_window_size()?;
+        Ok(Editor {
+            term: term,
+            screen: screen,
+            screen_rows: (rows as usize).saturating_sub(2),
+            screen_cols: cols as usize,
+            cursor_row: 0,
+            cursor_col: 0,
+            row_offset: 0,
+            col_offset: 0,
+            rows: vec![],
+            path: None,
+            syntax: None,
+            dirty: false,
+            quit_pending: false,
+            message: "HELP: Ctrl-S = save | Ctrl-Q = quit | Ctrl-F = find".to_string(),
+            message_time: Instant::now(),
+            search_match: None,
+            pending_utf8: Vec::new(),
+        })
+    }
+
+    pub fn open<P>(&mut self, path: P) -> Result<(), io::Error>
+    where
+        P: AsRef<path::Path>,
+    {
+        let file = fs::File::open(&path)?;
+        let reader = io::BufReader::new(file);
+        self.rows = reader.lines().collect::<Result<Vec<_>, _>>()?;
+        self.path = Some(path.as_ref().to_path_buf());
+        self.syntax = syntax_for_path(path.as_ref());
+        self.dirty = false;
+        Ok(())
+    }
+
+    fn set_message(&mut self, message: String) {
+        self.message = message;
+        self.message_time = Instant::now();
+    }
+
+    /// Reads a line of input through the existing keypress loop, echoing
+    /// it on the last screen row, and reports each keystroke to
+    /// `callback` so callers like `find` can react as the user types.
+    /// Returns `None` if the user cancels with `Key::Escape`.
+    fn prompt<F>(&mut self, message: &str, mut callback: F) -> Result<Option<String>, io::Error>
+    where
+        F: FnMut(&mut Editor, &str, &Key),
+    {
+        let mut input = String::new();
+        loop {
+            self.refresh_screen()?;
+
+            self.term.begin();
+            self.term.move_cursor_at(self.screen_rows + 2, 1);
+            self.term.erase_in_line();
+            self.term.push_str(message);
+            self.term.push_str(&input);
+            self.term.end()?;
+
+            let key = self.term.read_key()?;
+            match key {
+                Key::Char(b'\r') => if !input.is_empty() {
+                    callback(self, &input, &key);
+                    return Ok(Some(input));
+                },
+                Key::Escape => {
+                    callback(self, &input, &key);
+                    return Ok(None);
+                }
+                Key::Char(c) if c == BACKSPACE || c == CTRL_H => {
+                    input.pop();
+                }
+                Key::Char(c) if c >= 32 && c < 127 => input.push(c as char),
+                _ => (),
+            }
+            callback(self, &input, &key);
+        }
+    }
+
+    fn save(&mut self) -> Result<(), io::Error> {
+        if self.path.is_none() {
+            self.path = match self.prompt("Save as: ", |_, _, _| ())? {
+                Some(input) => Some(path::PathBuf::from(input)),
+                None => {
+                    self.set_message("Save aborted".to_string());
+                    return Ok(());
+                }
+            };
+            self.syntax = self.path.as_ref().and_then(|path| syntax_for_path(path));
+        }
+
+        let mut bytes_written = 0;
+        let mut file = fs::File::create(self.path.as_ref().unwrap())?;
+        for row in &self.rows {
+            file.write_all(row.as_bytes())?;
+            file.write_all(b"\n")?;
+            bytes_written += row.len() + 1;
+        }
+        self.dirty = false;
+        self.set_message(format!("{} bytes written to disk", bytes_written));
+        Ok(())
+    }
+
+    /// Incremental search: scans `rows` after every keystroke of the
+    /// query, jumping to the next/previous match on the arrow keys and
+    /// restoring the prior cursor position if the user cancels.
+    fn find(&mut self) -> Result<(), io::Error> {
+        let saved_cursor_row = self.cursor_row;
+        let saved_cursor_col = self.cursor_col;
+        let saved_row_offset = self.row_offset;
+        let saved_col_offset = self.col_offset;
+
+        let mut last_match: Option<usize> = None;
+        let mut direction: isize = 1;
+
+        let result = self.prompt("Search: ", |editor, query, key| {
+            // Enter/Escape confirm or cancel the current position; neither
+            // should start a fresh scan from the first match.
+            if *key == Key::Char(b'\r') || *key == Key::Escape {
+                return;
+            }
+
+            match *key {
+                Key::Right | Key::Down => direction = 1,
+                Key::Left | Key::Up => direction = -1,
+                _ => {
+                    last_match = None;
+                    direction = 1;
+                }
+            }
+
+            editor.search_match = None;
+
+            if query.is_empty() || editor.rows.is_empty() {
+                return;
+            }
+
+            let mut current = match last_match {
+                Some(row) => row as isize,
+                None => -1,
+            };
+
+            for _ in 0..editor.rows.len() {
+                current += direction;
+                if current < 0 {
+                    current = editor.rows.len() as isize - 1;
+                } else if current == editor.rows.len() as isize {
+                    current = 0;
+                }
+
+                let row = current as usize;
+                if let Some(col) = editor.rows[row].find(query) {
+                    last_match = Some(row);
+                    editor.cursor_row = row;
+                    editor.cursor_col = col;
+                    editor.search_match = Some((row, col, query.len()));
+                    // Force scroll() to put the match on the top screen row.
+                    editor.row_offset = editor.rows.len();
+                    break;
+                }
+            }
+        })?;
+
+        self.search_match = None;
+        if result.is_none() {
+            self.cursor_row = saved_cursor_row;
+            self.cursor_col = saved_cursor_col;
+            self.row_offset = saved_row_offset;
+            self.col_offset = saved_col_offset;
+        }
+
+        Ok(())
+    }
+
+    fn insert_char(&mut self, c: u8) {
+        // The terminal feeds multi-byte UTF-8 characters one raw byte per
+        // keystroke, so a lead byte and its continuation bytes arrive as
+        // separate `Key::Char`s. Buffer them here and only insert once a
+        // whole char has been assembled.
+        self.pending_utf8.push(c);
+        let ch = match str::from_utf8(&self.pending_utf8) {
+            Ok(s) => {
+                let ch = s.chars().next().unwrap();
+                self.pending_utf8.clear();
+                ch
+            }
+            Err(err) if err.error_len().is_none() => return,
+            Err(_) => {
+                self.pending_utf8.clear();
+                return;
+            }
+        };
+
+        if self.cursor_row == self.rows.len() {
+            self.rows.push(String::new());
+        }
+        let col = self.cursor_col.min(self.rows[self.cursor_row].len());
+        self.rows[self.cursor_row].insert(col, ch);
+        self.cursor_col = col + ch.len_utf8();
+        self.dirty = true;
+    }
+
+    fn insert_newline(&mut self) {
+        if self.cursor_row == self.rows.len() {
+            self.rows.push(String::new());
+        }
+        let col = self.cursor_col.min(self.rows[self.cursor_row].len());
+        let rest = self.rows[self.cursor_row].split_off(col);
+        self.rows.insert(self.cursor_row + 1, rest);
+        self.cursor_row += 1;
+        self.cursor_col = 0;
+        self.dirty = true;
+    }
+
+    fn delete_char(&mut self) {
+        if self.cursor_row >= self.rows.len() {
+            return;
+        }
+        if self.cursor_col == 0 && self.cursor_row == 0 {
+            return;
+        }
+        if self.cursor_col > 0 {
+            let col = self.cursor_col.min(self.rows[self.cursor_row].len());
+            if col > 0 {
+                let prev = self.rows[self.cursor_row][..col]
+                    .char_indices()
+                    .next_back()
+                    .map_or(0, |(i, _)| i);
+                self.rows[self.cursor_row].remove(prev);
+                self.cursor_col = prev;
+            }
+        } else {
+            let row = self.rows.remove(self.cursor_row);
+            self.cursor_row -= 1;
+            self.cursor_col = self.rows[self.cursor_row].len();
+            self.rows[self.cursor_row].push_str(&row);
+        }
+        self.dirty = true;
+    }
+
+    fn move_cursor(&mut self, key: Key) {
+        match key {
+            Key::Left => if self.cursor_col != 0 {
+                let back = self.rows[self.cursor_row][..self.cursor_col]
+                    .chars()
+                    .next_back()
+                    .map_or(1, |ch| ch.len_utf8());
+                self.cursor_col -= back;
+            } else if self.cursor_row != 0 {
+                self.cursor_row -= 1;
+                self.cursor_col = self.rows[self.cursor_row].len();
+            },
+            Key::Right => {
+                let len = self.rows.get(self.cursor_row).map_or(0, |row| row.len());
+                if self.cursor_col < len {
+                    let ahead = self.rows[self.cursor_row][self.cursor_col..]
+                        .chars()
+                        .next()
+                        .map_or(1, |ch| ch.len_utf8());
+                    self.cursor_col += ahead;
+                } else if self.cursor_row < self.rows.len() {
+                    self.cursor_row += 1;
+                    self.cursor_col = 0;
+                }
+            }
+            Key::Up => if self.cursor_row != 0 {
+                self.cursor_row -= 1
+            },
+            Key::Down => if self.cursor_row < self.rows.len() {
+                self.cursor_row += 1
+            },
+            _ => (),
+        }
+
+        let len = self.rows.get(self.cursor_row).map_or(0, |row| row.len());
+        if self.cursor_col > len {
+            self.cursor_col = len;
+        }
+    }
+
+    fn scroll(&mut self) {
+        if self.cursor_row < self.row_offset {
+            self.row_offset = self.cursor_row;
+        }
+        if self.cursor_row >= self.row_offset + self.screen_rows {
+            self.row_offset = self.cursor_row - self.screen_rows + 1;
+        }
+
+        let rx = self.rows
+            .get(self.cursor_row)
+            .map_or(0, |row| cx_to_rx(row, self.cursor_col));
+        if rx < self.col_offset {
+            self.col_offset = rx;
+        }
+        if rx >= self.col_offset + self.screen_cols {
+            self.col_offset = rx - self.screen_cols + 1;
+        }
+    }
+
+    fn draw_rows(&mut self) {
+        for y in 0..self.screen_rows {
+            let file_row = y + self.row_offset;
+            if file_row >= self.rows.len() {
+                if self.rows.is_empty() && y == self.screen_rows / 3 {
+                    let welcome = format!("Kilo editor -- version {}", VERSION);
+                    let len = welcome.len().min(self.screen_cols);
+                    let mut padding = (self.screen_cols - len) / 2;
+                    if padding > 0 {
+                        self.term.push('~');
+                        padding -= 1;
+                    }
+                    for _ in 0..padding {
+                        self.term.push(' ');
+                    }
+                    self.term.push_str(&welcome[..len]);
+                } else {
+                    self.term.push('~');
+                }
+            } else {
+                let row = &self.rows[file_row];
+                let mut hl = highlight_row(row, self.syntax);
+                if let Some((match_row, match_col, match_len)) = self.search_match {
+                    if match_row == file_row {
+                        for h in hl[match_col..match_col + match_len].iter_mut() {
+                            *h = Highlight::Match;
+                        }
+                    }
+                }
+                let (rendered, rhl) = render_row(row, &hl);
+
+                let start = cmp::min(self.col_offset, rendered.len());
+                let end = cmp::min(self.col_offset + self.screen_cols, rendered.len());
+
+                let mut current = Highlight::Normal;
+                for (&ch, &h) in rendered[start..end].iter().zip(rhl[start..end].iter()) {
+                    if h != current {
+                        self.term.set_foreground_color(h.color());
+                        current = h;
+                    }
+                    self.term.push(ch);
+                }
+                if current != Highlight::Normal {
+                    self.term.reset_foreground_color();
+                }
+            }
+            self.term.erase_in_line();
+            self.term.push_str("\r\n");
+        }
+    }
+
+    fn draw_status_bar(&mut self) {
+        self.term.set_reverse_video();
+
+        let filename = self.path
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .and_then(|name| name.to_str())
+            .unwrap_or("[No Name]");
+        let dirty = if self.dirty { " (modified)" } else { "" };
+        let mut status = format!("{}{} - {} lines", filename, dirty, self.rows.len());
+        status.truncate(self.screen_cols);
+        let line_info = format!("{}/{}", self.cursor_row + 1, self.rows.len());
+
+        self.term.push_str(&status);
+        let mut len = status.len();
+        while len < self.screen_cols {
+            if self.screen_cols - len == line_info.len() {
+                self.term.push_str(&line_info);
+                break;
+            }
+            self.term.push(' ');
+            len += 1;
+        }
+
+        self.term.reset_mode();
+        self.term.push_str("\r\n");
+    }
+
+    fn draw_message_bar(&mut self) {
+        self.term.erase_in_line();
+        if self.message_time.elapsed() < Duration::from_secs(5) {
+            let len = cmp::min(self.message.len(), self.screen_cols);
+            self.term.push_str(&self.message[..len]);
+        }
+    }
+
+    fn refresh_screen(&mut self) -> Result<(), io::Error> {
+        self.scroll();
+
+        self.term.begin();
+
+        self.term.hide_cursor();
+        self.term.move_cursor();
+
+        self.draw_rows();
+        self.draw_status_bar();
+        self.draw_message_bar();
+
+        let rx = self.rows
+            .get(self.cursor_row)
+            .map_or(0, |row| cx_to_rx(row, self.cursor_col));
+        self.term.move_cursor_at(
+            self.cursor_row - self.row_offset + 1,
+            rx - self.col_offset + 1,
+        );
+        self.term.show_cursor();
+
+        self.term.end()?;
+        Ok(())
+    }
+
+    pub fn run(&mut self) -> Result<(), io::Error> {
+        loop {
+            self.refresh_screen()?;
+            let key = self.term.read_key()?;
+            match key {
+                Key::Up | Key::Down | Key::Right | Key::Left => self.move_cursor(key),
+                Key::PageUp | Key::PageDown => {
+                    let times = self.screen_rows;
+                    for _ in 0..times {
+                        self.move_cursor(if key == Key::PageUp {
+                            Key::Up
+                        } else {
+                            Key::Down
+                        });
+                    }
+                }
+                Key::Home => self.cursor_col = 0,
+                Key::End => {
+                    self.cursor_col = self.rows.get(self.cursor_row).map_or(0, |row| row.len())
+                }
+                Key::Char(CTRL_Q) => {
+                    if self.dirty && !self.quit_pending {
+                        self.quit_pending = true;
+                        self.set_message(
+                            "Unsaved changes! Press Ctrl-Q again to quit.".to_string(),
+                        );
+                        continue;
+                    }
+                    self.term.begin();
+                    self.term.erase_in_display();
+                    self.term.move_cursor();
+                    self.term.end()?;
+                    return Ok(());
+                }
+                Key::Char(CTRL_S) => self.save()?,
+                Key::Char(CTRL_F) => self.find()?,
+                Key::Char(b'\r') => self.insert_newline(),
+                Key::Delete => {
+                    self.move_cursor(Key::Right);
+                    self.delete_char();
+                }
+                Key::Char(c) if c == BACKSPACE || c == CTRL_H => self.delete_char(),
+                Key::Char(c) if c >= 32 && c != BACKSPACE => self.insert_char(c),
+                _ => (),
+            }
+            if key != Key::Char(CTRL_Q) {
+                self.quit_pending = false;
+            }
+        }
+    }
+}
+
+pub mod platform {
+
+    #[cfg(unix)]
+    pub mod unix {
+        extern crate libc;
+
+        use super::super::Key;
+        use std::io;
+        use std::io::prelude::*;
+        use std::mem;
+
+        fn tcgetattr() -> Result<libc::termios, io::Error> {
+            let mut termios = unsafe { mem::uninitialized() };
+            if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut termios) } == 0 {
+                Ok(termios)
+            } else {
+                Err(io::Error::last_os_error())
+            }
+        }
+
+        fn tcsetattr(termios: &libc::termios) -> Result<(), io::Error> {
+            if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSAFLUSH, termios) } == 0 {
+                Ok(())
+            } else {
+                Err(io::Error::last_os_error())
+            }
+        }
+
+        #[macro_use]
+        mod vt100 {
+            use std::io;
+            use std::io::prelude::*;
+            use std::str;
+
+            macro_rules! csi {
+            ($cmd:expr) => {
+                concat!("\x1b[", $cmd)
+            };
+            ($fmt:expr, $($args:tt)*) => {
+                format!(concat!("\x1b[", $fmt), $($args)*)
+            };
+        }
+            macro_rules! cursor_forward {
+                ($n:expr) => {
+                    csi!(concat!($n, "C"))
+                };
+            }
+            macro_rules! cursor_down {
+                ($n:expr) => {
+                    csi!(concat!($n, "B"))
+                };
+            }
+            macro_rules! cursor_position {
+                () => {
+                    csi!("H")
+                };
+                ($row:expr, $col:expr) => {
+                    csi!("{};{}H", $row, $col)
+                };
+            }
+            macro_rules! erase_in_display {
+                () => {
+                    csi!("2J")
+                };
+            }
+            macro_rules! erase_in_line {
+                () => {
+                    csi!("K")
+                };
+            }
+            macro_rules! report_device_status {
+                (active_position) => {
+                    csi!(concat!("6n"))
+                };
+            }
+            macro_rules! set_mode {
+                (hide_cursor) => {
+                    csi!("?25l")
+                };
+                (show_cursor) => {
+                    csi!("?25h")
+                };
+                (alternate_screen) => {
+                    csi!("?1049h")
+                };
+                (normal_screen) => {
+                    csi!("?1049l")
+                };
+                (reverse_video) => {
+                    csi!("7m")
+                };
+                (reset) => {
+                    csi!("0m")
+                };
+            }
+            macro_rules! set_foreground_color {
+                ($n:expr) => {
+                    csi!("{}m", $n)
+                };
+            }
+
+            pub fn get_cursor_position(
+                stdin: io::StdinLock,
+                mut stdout: io::StdoutLock,
+            ) -> Result<(u16, u16), io::Error> {
+                stdout.write_all(
+                    concat!(
+                        cursor_forward!(999),
+                        cursor_down!(999),
+                        report_device_status!(active_position)
+                    ).as_bytes(),
+                )?;
+                stdout.flush()?;
+
+                let mut buf = vec![];
+                let read = stdin.take(2 + 5 + 1 + 5 + 1).read_until(b'R', &mut buf)?;
+
+                let bad_cpr =
+                    || io::Error::new(io::ErrorKind::Other, format!("bad CPR: {:?}", buf));
+                if read < 5 || read > 2 + 5 + 1 + 5 {
+                    return Err(bad_cpr());
+                }
+                if buf[0] != b'\x1b' || buf[1] != b'[' {
+                    return Err(bad_cpr());
+                }
+                let mid = buf.iter().position(|&b| b == b';').ok_or_else(bad_cpr)?;
+                let rows = unsafe {
+                    str::from_utf8_unchecked(&buf[2..mid])
+                        .parse()
+                        .map_err(|_| bad_cpr())?
+                };
+                let cols = unsafe {
+                    str::from_utf8_unchecked(&buf[mid + 1..read - 1])
+                        .parse()
+                        .map_err(|_| bad_cpr())?
+                };
+
+                return Ok((rows, cols));
+            }
+        }
+
+        /// States of the escape-sequence decoder, carried across calls so a
+        /// multi-byte sequence can straddle several reads.
+        enum DecodeState {
+            Ground,
+            Escape,
+            Csi,
+            CsiDigit(u8),
+            Ss3,
+        }
+
+        /// Turns a stream of raw input bytes into `Key`s one byte at a
+        /// time. Shared by `blocking` and `unblocking` so both read paths
+        /// agree on how escape sequences are recognised.
+        struct KeyDecoder {
+            state: DecodeState,
+        }
+
+        impl KeyDecoder {
+            fn new() -> KeyDecoder {
+                KeyDecoder {
+                    state: DecodeState::Ground,
+                }
+            }
+
+            /// True while a partial escape sequence is buffered, i.e. a
+            /// missing next byte should resolve to a bare `Key::Escape`
+            /// rather than be treated as "no input yet".
+            fn in_progress(&self) -> bool {
+                match self.state {
+                    DecodeState::Ground => false,
+                    _ => true,
+                }
+            }
+
+            fn reset(&mut self) {
+                self.state = DecodeState::Ground;
+            }
+
+            fn push(&mut self, b: u8) -> Option<Key> {
+                match self.state {
+                    DecodeState::Ground => if b == b'\x1b' {
+                        self.state = DecodeState::Escape;
+                        None
+                    } else {
+                        Some(Key::Char(b))
+                    },
+                    DecodeState::Escape => {
+                        self.state = DecodeState::Ground;
+                        match b {
+                            b'[' => {
+                                self.state = DecodeState::Csi;
+                                None
+                            }
+                            b'O' => {
+                                self.state = DecodeState::Ss3;
+                                None
+                            }
+                            _ => Some(Key::Escape),
+                        }
+                    }
+                    DecodeState::Csi => {
+                        self.state = DecodeState::Ground;
+                        match b {
+                            b'A' => Some(Key::Up),
+                            b'B' => Some(Key::Down),
+                            b'C' => Some(Key::Right),
+                            b'D' => Some(Key::Left),
+                            b'H' => Some(Key::Home),
+                            b'F' => Some(Key::End),
+                            b @ b'0'...b'9' => {
+                                self.state = DecodeState::CsiDigit(b);
+                                None
+                            }
+                            _ => Some(Key::Escape),
+                        }
+                    }
+                    DecodeState::CsiDigit(digit) => {
+                        self.state = DecodeState::Ground;
+                        if b == b'~' {
+                            match digit {
+                                b'1' | b'7' => Some(Key::Home),
+                                b'3' => Some(Key::Delete),
+                                b'4' | b'8' => Some(Key::End),
+                                b'5' => Some(Key::PageUp),
+                                b'6' => Some(Key::PageDown),
+                                _ => Some(Key::Escape),
+                            }
+                        } else {
+                            Some(Key::Escape)
+                        }
+                    }
+                    DecodeState::Ss3 => {
+                        self.state = DecodeState::Ground;
+                        match b {
+                            b'H' => Some(Key::Home),
+                            b'F' => Some(Key::End),
+                            _ => Some(Key::Escape),
+                        }
+                    }
+                }
+            }
+        }
+
+        /// The raw-mode setup and output buffering shared by the blocking
+        /// and unblocking terminals; only the way input bytes are sourced
+        /// differs between them.
+        struct Common {
+            orig: libc::termios,
+            stdout: io::Stdout,
+            buf: String,
+        }
+
+        impl Common {
+            fn new_raw_mode() -> Result<Common, io::Error> {
+                let orig = tcgetattr()?;
+
+                let mut raw = orig;
+
+                raw.c_iflag &=
+                    !(libc::BRKINT | libc::ICRNL | libc::INPCK | libc::ISTRIP | libc::IXON);
+                raw.c_oflag &= !libc::OPOST;
+                raw.c_cflag |= libc::CS8;
+                raw.c_lflag &= !(libc::ECHO | libc::ICANON | libc::IEXTEN | libc::ISIG);
+                raw.c_cc[libc::VMIN] = 0;
+                raw.c_cc[libc::VTIME] = 1;
+
+                tcsetattr(&raw)?;
+
+                Ok(Common {
+                    orig: orig,
+                    stdout: io::stdout(),
+                    buf: String::new(),
+                })
+            }
+
+            fn get_window_size(&self, stdin: io::StdinLock) -> Result<(u16, u16), io::Error> {
+                let ws: libc::winsize = unsafe { mem::uninitialized() };
+                if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &ws) } == -1 {
+                    vt100::get_cursor_position(stdin, self.stdout.lock())
+                } else {
+                    Ok((ws.ws_row, ws.ws_col))
+                }
+            }
+
+            fn begin(&mut self) {
+                self.buf = String::new();
+            }
+
+            fn end(&self) -> Result<(), io::Error> {
+                let mut stdout = io::stdout();
+                stdout.write_all(self.buf.as_bytes())?;
+                stdout.flush()
+            }
+
+            fn erase_in_display(&mut self) {
+                self.buf.push_str(erase_in_display!());
+            }
+
+            fn erase_in_line(&mut self) {
+                self.buf.push_str(erase_in_line!());
+            }
+
+            fn hide_cursor(&mut self) {
+                self.buf.push_str(set_mode!(hide_cursor))
+            }
+
+            fn show_cursor(&mut self) {
+                self.buf.push_str(set_mode!(show_cursor))
+            }
+
+            fn set_reverse_video(&mut self) {
+                self.buf.push_str(set_mode!(reverse_video))
+            }
+
+            fn reset_mode(&mut self) {
+                self.buf.push_str(set_mode!(reset))
+            }
+
+            fn set_foreground_color(&mut self, color: u8) {
+                self.buf.push_str(&set_foreground_color!(color));
+            }
+
+            fn reset_foreground_color(&mut self) {
+                self.set_foreground_color(39);
+            }
+
+            fn move_cursor(&mut self) {
+                self.buf.push_str(cursor_position!())
+            }
+
+            fn move_cursor_at(&mut self, row: usize, col: usize) {
+                self.buf.push_str(&cursor_position!(row, col))
+            }
+
+            fn push(&mut self, ch: char) {
+                self.buf.push(ch);
+            }
+
+            fn push_str(&mut self, s: &str) {
+                self.buf.push_str(s);
+            }
+        }
+
+        impl Drop for Common {
+            fn drop(&mut self) {
+                tcsetattr(&self.orig).unwrap();
+            }
+        }
+
+        /// Today's busy-polling `Terminal`, driven by a plain `loop`.
+        pub mod blocking {
+            extern crate libc;
+
+            use super::super::super::Key;
+            use super::{Common, KeyDecoder};
+            use std::io;
+            use std::io::prelude::*;
+
+            pub struct Terminal {
+                common: Common,
+                stdin: io::Stdin,
+            }
+
+            impl Terminal {
+                pub fn new_raw_mode() -> Result<Terminal, io::Error> {
+                    Ok(Terminal {
+                        common: Common::new_raw_mode()?,
+                        stdin: io::stdin(),
+                    })
+                }
+
+                pub fn get_window_size(&self) -> Result<(u16, u16), io::Error> {
+                    self.common.get_window_size(self.stdin.lock())
+                }
+
+                pub fn read_key(&self) -> Result<Key, io::Error> {
+                    let stdin = self.stdin.lock();
+                    let mut bytes = stdin.bytes().filter(|x| {
+                        x.as_ref()
+                            .err()
+                            .and_then(io::Error::raw_os_error)
+                            .map(|raw_os_error| raw_os_error != libc::EAGAIN)
+                            .unwrap_or(true)
+                    });
+                    let mut decoder = KeyDecoder::new();
+                    loop {
+                        match bytes.next() {
+                            Some(next) => {
+                                let b = next?;
+                                if let Some(key) = decoder.push(b) {
+                                    return Ok(key);
+                                }
+                            }
+                            None => if decoder.in_progress() {
+                                decoder.reset();
+                                return Ok(Key::Escape);
+                            },
+                        }
+                    }
+                }
+
+                pub fn begin(&mut self) {
+                    self.common.begin();
+                }
+
+                pub fn end(&self) -> Result<(), io::Error> {
+                    self.common.end()
+                }
+
+                pub fn erase_in_display(&mut self) {
+                    self.common.erase_in_display();
+                }
+
+                pub fn erase_in_line(&mut self) {
+                    self.common.erase_in_line();
+                }
+
+                pub fn hide_cursor(&mut self) {
+                    self.common.hide_cursor();
+                }
+
+                pub fn show_cursor(&mut self) {
+                    self.common.show_cursor();
+                }
+
+                pub fn set_reverse_video(&mut self) {
+                    self.common.set_reverse_video();
+                }
+
+                pub fn reset_mode(&mut self) {
+                    self.common.reset_mode();
+                }
+
+                pub fn set_foreground_color(&mut self, color: u8) {
+                    self.common.set_foreground_color(color);
+                }
+
+                pub fn reset_foreground_color(&mut self) {
+                    self.common.reset_foreground_color();
+                }
+
+                pub fn move_cursor(&mut self) {
+                    self.common.move_cursor();
+                }
+
+                pub fn move_cursor_at(&mut self, row: usize, col: usize) {
+                    self.common.move_cursor_at(row, col);
+                }
+
+                pub fn push(&mut self, ch: char) {
+                    self.common.push(ch);
+                }
+
+                pub fn push_str(&mut self, s: &str) {
+                    self.common.push_str(s);
+                }
+            }
+        }
+
+        /// An `async`-friendly `Terminal` that registers stdin for
+        /// readiness with the runtime instead of busy-polling it, so it
+        /// can be `.await`ed alongside timers or network events in one
+        /// `select!`. Feeds the same `KeyDecoder` as `blocking`, so both
+        /// read paths agree on how escape sequences are recognised. See
+        /// `examples/async_driver.rs` for a worked integration.
+        pub mod unblocking {
+            extern crate libc;
+            extern crate tokio;
+
+            use self::tokio::io::unix::AsyncFd;
+            use self::tokio::time::{timeout, Duration};
+            use super::super::super::Key;
+            use super::{Common, KeyDecoder};
+            use std::io;
+            use std::io::prelude::*;
+
+            pub struct Terminal {
+                common: Common,
+                stdin: AsyncFd<io::Stdin>,
+            }
+
+            impl Terminal {
+                pub fn new_raw_mode() -> Result<Terminal, io::Error> {
+                    Ok(Terminal {
+                        common: Common::new_raw_mode()?,
+                        stdin: AsyncFd::new(io::stdin())?,
+                    })
+                }
+
+                pub fn get_window_size(&self) -> Result<(u16, u16), io::Error> {
+                    self.common.get_window_size(io::stdin().lock())
+                }
+
+                pub async fn read_key(&mut self) -> Result<Key, io::Error> {
+                    let mut decoder = KeyDecoder::new();
+                    loop {
+                        let mut guard = if decoder.in_progress() {
+                            // A partial escape sequence is waiting on its
+                            // next byte: give it a short grace period, then
+                            // resolve to a bare Escape instead of hanging.
+                            match timeout(Duration::from_millis(50), self.stdin.readable_mut())
+                                .await
+                            {
+                                Ok(guard) => guard?,
+                                Err(_elapsed) => {
+                                    decoder.reset();
+                                    return Ok(Key::Escape);
+                                }
+                            }
+                        } else {
+                            self.stdin.readable_mut().await?
+                        };
+
+                        let mut byte = [0u8; 1];
+                        match guard.try_io(|inner| inner.get_mut().read(&mut byte)) {
+                            Ok(Ok(0)) => continue,
+                            Ok(Ok(_)) => if let Some(key) = decoder.push(byte[0]) {
+                                return Ok(key);
+                            },
+                            Ok(Err(err)) => return Err(err),
+                            Err(_would_block) => continue,
+                        }
+                    }
+                }
+
+                pub fn begin(&mut self) {
+                    self.common.begin();
+                }
+
+                pub fn end(&self) -> Result<(), io::Error> {
+                    self.common.end()
+                }
+
+                pub fn erase_in_display(&mut self) {
+                    self.common.erase_in_display();
+                }
+
+                pub fn erase_in_line(&mut self) {
+                    self.common.erase_in_line();
+                }
+
+                pub fn hide_cursor(&mut self) {
+                    self.common.hide_cursor();
+                }
+
+                pub fn show_cursor(&mut self) {
+                    self.common.show_cursor();
+                }
+
+                pub fn set_reverse_video(&mut self) {
+                    self.common.set_reverse_video();
+                }
+
+                pub fn reset_mode(&mut self) {
+                    self.common.reset_mode();
+                }
+
+                pub fn set_foreground_color(&mut self, color: u8) {
+                    self.common.set_foreground_color(color);
+                }
+
+                pub fn reset_foreground_color(&mut self) {
+                    self.common.reset_foreground_color();
+                }
+
+                pub fn move_cursor(&mut self) {
+                    self.common.move_cursor();
+                }
+
+                pub fn move_cursor_at(&mut self, row: usize, col: usize) {
+                    self.common.move_cursor_at(row, col);
+                }
+
+                pub fn push(&mut self, ch: char) {
+                    self.common.push(ch);
+                }
+
+                pub fn push_str(&mut self, s: &str) {
+                    self.common.push_str(s);
+                }
+            }
+        }
+
+        /// Switches the terminal to the alternate screen buffer on
+        /// construction and back to the original screen on drop, so the
+        /// user's prior terminal contents survive a run of the editor.
+        pub struct ScreenGuard;
+
+        impl ScreenGuard {
+            pub fn new() -> Result<ScreenGuard, io::Error> {
+                let mut stdout = io::stdout();
+                stdout.write_all(set_mode!(alternate_screen).as_bytes())?;
+                stdout.flush()?;
+                Ok(ScreenGuard)
+            }
+        }
+
+        impl Drop for ScreenGuard {
+            fn drop(&mut self) {
+                let mut stdout = io::stdout();
+                let _ = stdout.write_all(set_mode!(normal_screen).as_bytes());
+                let _ = stdout.flush();
+            }
+        }
+
+    }
+}
+
+#[cfg(unix)]
+use platform::unix::blocking as target;
+#[cfg(unix)]
+use platform::unix::ScreenGuard;
+
+/// Opens `path` (if given as the first CLI argument) and runs the editor
+/// to completion on the blocking terminal. Split out of `main`, which
+/// only reports the error and sets the exit status.
+pub fn run() -> Result<(), io::Error> {
+    let mut editor = Editor::new()?;
+
+    let mut args = env::args();
+    if let Some(path) = args.nth(1) {
+        editor.open(path)?;
+    }
+
+    editor.run()
+}